@@ -0,0 +1,294 @@
+// An entity-attribute-value alternative to the table-per-entity `SQLable`
+// design in the parent module. Every field is a row in a single `datoms`
+// table instead of a column in a type-specific table, and a query is a set
+// of `[entity attribute value]` patterns compiled into one self-join of
+// `datoms` - one aliased join per pattern, one shared alias per variable.
+use super::Rusqlite;
+
+use rusqlite::{NO_PARAMS, types::ToSql};
+
+use std::collections::HashMap;
+
+// A stored value, tagged with its own type so reading the dynamically-typed
+// `value` column back out is unambiguous (SQLite alone can't tell a `1`
+// meant as a bool apart from a `1` meant as an int).
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Text(String),
+    Int(i64),
+    Real(f64),
+    Bool(bool),
+}
+
+impl Value {
+    fn type_tag(&self) -> &'static str {
+        match self {
+            Value::Text(_) => "text",
+            Value::Int(_) => "int",
+            Value::Real(_) => "real",
+            Value::Bool(_) => "bool",
+        }
+    }
+
+    fn boxed(&self) -> Box<ToSql> {
+        match self {
+            Value::Text(v) => Box::new(v.clone()),
+            Value::Int(v) => Box::new(*v),
+            Value::Real(v) => Box::new(*v),
+            Value::Bool(v) => Box::new(*v),
+        }
+    }
+}
+
+impl From<&str> for Value {
+    fn from(v: &str) -> Self { Value::Text(v.to_string()) }
+}
+impl From<String> for Value {
+    fn from(v: String) -> Self { Value::Text(v) }
+}
+impl From<i64> for Value {
+    fn from(v: i64) -> Self { Value::Int(v) }
+}
+impl From<f64> for Value {
+    fn from(v: f64) -> Self { Value::Real(v) }
+}
+impl From<bool> for Value {
+    fn from(v: bool) -> Self { Value::Bool(v) }
+}
+
+// One position of a `[entity attribute value]` pattern: either bound to a
+// concrete value (an equality constraint) or an unbound variable (a column
+// to join against every other occurrence of the same name, and to project
+// in the result).
+#[derive(Debug, Clone)]
+pub enum Term {
+    Var(&'static str),
+    Bound(Value),
+}
+
+impl Term {
+    pub fn var(name: &'static str) -> Self {
+        Term::Var(name)
+    }
+
+    pub fn val<V: Into<Value>>(value: V) -> Self {
+        Term::Bound(value.into())
+    }
+}
+
+// `[?u "user/name" "Mike"]`, `[?u "user/task" ?t]`, `[?t "task/done" true]`
+pub struct Pattern {
+    pub entity: Term,
+    pub attribute: Term,
+    pub value: Term,
+}
+
+impl Pattern {
+    pub fn new(entity: Term, attribute: Term, value: Term) -> Self {
+        Pattern { entity: entity, attribute: attribute, value: value }
+    }
+}
+
+// Which column of a `datoms` row a pattern position binds to. `Val` is the
+// only one that's polymorphic (hence the `value_type` tag column); `Entity`
+// and `Attribute` are always an integer id and a plain attribute name.
+#[derive(Clone, Copy, PartialEq)]
+enum Role { Entity, Attribute, Val }
+
+impl Role {
+    fn column(self) -> &'static str {
+        match self {
+            Role::Entity => "entity_id",
+            Role::Attribute => "attribute",
+            Role::Val => "value",
+        }
+    }
+}
+
+// A variable's first occurrence: which join alias and column it's bound to,
+// so later occurrences can be joined against it instead of projected again.
+struct Binding {
+    var: &'static str,
+    role: Role,
+    alias: String,
+}
+
+// Renders one pattern position, either pushing an equality constraint (bound
+// term) or recording/joining a variable (unbound term), onto the shared
+// accumulators every pattern in the query contributes to.
+fn bind_term(
+    term: &Term,
+    role: Role,
+    alias: &str,
+    seen: &mut HashMap<&'static str, (String, Role)>,
+    constraints: &mut Vec<String>,
+    params: &mut Vec<Box<ToSql>>,
+    bindings: &mut Vec<Binding>,
+) {
+    match term {
+        Term::Bound(value) => {
+            if role == Role::Val {
+                constraints.push(format!("{}.value_type = ?", alias));
+                params.push(Box::new(value.type_tag().to_string()));
+            }
+            constraints.push(format!("{}.{} = ?", alias, role.column()));
+            params.push(value.boxed());
+        }
+        Term::Var(name) => match seen.get(name) {
+            Some((seen_alias, seen_role)) => {
+                constraints.push(format!("{}.{} = {}.{}", alias, role.column(), seen_alias, seen_role.column()));
+            }
+            None => {
+                seen.insert(name, (alias.to_string(), role));
+                bindings.push(Binding { var: name, role: role, alias: alias.to_string() });
+            }
+        },
+    }
+}
+
+// Compiles `patterns` into one self-join of `datoms`: a `FROM` list with one
+// aliased occurrence per pattern, a `WHERE` built from each position's
+// constraint, the bound parameters in the same order, and the variable
+// bindings `query_datoms` needs to project and decode the result rows.
+fn compile(patterns: &[Pattern]) -> (String, Vec<Box<ToSql>>, Vec<Binding>) {
+    let mut froms = Vec::with_capacity(patterns.len());
+    let mut constraints = Vec::new();
+    let mut params: Vec<Box<ToSql>> = Vec::new();
+    let mut seen: HashMap<&'static str, (String, Role)> = HashMap::new();
+    let mut bindings = Vec::new();
+
+    for (i, pattern) in patterns.iter().enumerate() {
+        let alias = format!("d{}", i);
+        froms.push(format!("datoms {}", alias));
+
+        bind_term(&pattern.entity, Role::Entity, &alias, &mut seen, &mut constraints, &mut params, &mut bindings);
+        bind_term(&pattern.attribute, Role::Attribute, &alias, &mut seen, &mut constraints, &mut params, &mut bindings);
+        bind_term(&pattern.value, Role::Val, &alias, &mut seen, &mut constraints, &mut params, &mut bindings);
+    }
+
+    let select = bindings.iter().map(|b| match b.role {
+        Role::Val => format!("{alias}.value_type AS \"{var}__type\", {alias}.value AS \"{var}__value\"", alias = b.alias, var = b.var),
+        _ => format!("{}.{} AS \"{}\"", b.alias, b.role.column(), b.var),
+    }).collect::<Vec<_>>().join(", ");
+
+    let mut sql = format!("SELECT {} FROM {}", select, froms.join(", "));
+    if !constraints.is_empty() {
+        sql += " WHERE ";
+        sql += &constraints.join(" AND ");
+    }
+
+    (sql, params, bindings)
+}
+
+impl Rusqlite {
+    pub fn setup_datoms(&self) -> Result<usize, rusqlite::Error> {
+        self.with_conn(|conn| conn.execute(
+            "CREATE TABLE datoms (
+                entity_id   INTEGER NOT NULL,
+                attribute   TEXT NOT NULL,
+                value_type  TEXT NOT NULL,
+                value
+            )",
+            NO_PARAMS,
+        ))
+    }
+
+    // Stores one `(entity_id, attribute, value)` fact. Doesn't take
+    // `write_lock` itself, same convention as `save_unlocked`/
+    // `save_all_unlocked` - `assert` below is the locked entry point.
+    fn assert_unlocked(&self, entity_id: i64, attribute: &'static str, value: Value) {
+        let attribute = attribute.to_string();
+        let type_tag = value.type_tag().to_string();
+        self.with_conn(|conn| {
+            let mut stmnt = conn.prepare("INSERT INTO datoms (entity_id, attribute, value_type, value) VALUES (?, ?, ?, ?)")
+                .expect("Could not prepare datom insert");
+
+            let result = match &value {
+                Value::Text(v) => stmnt.execute(&[&entity_id as &ToSql, &attribute, &type_tag, v]),
+                Value::Int(v) => stmnt.execute(&[&entity_id as &ToSql, &attribute, &type_tag, v]),
+                Value::Real(v) => stmnt.execute(&[&entity_id as &ToSql, &attribute, &type_tag, v]),
+                Value::Bool(v) => stmnt.execute(&[&entity_id as &ToSql, &attribute, &type_tag, v]),
+            };
+            result.expect("Could not insert datom");
+        });
+    }
+
+    // Locked entry point for `assert_unlocked`, same shape as
+    // `Repository::save` taking `write_lock` around `save_unlocked`.
+    pub fn assert(&mut self, entity_id: i64, attribute: &'static str, value: Value) {
+        let _write_guard = self.write_lock.lock().expect("Write lock poisoned");
+        self.assert_unlocked(entity_id, attribute, value);
+    }
+
+    // Binds `patterns` as one self-joined query and returns one map of
+    // variable name to its decoded `Value` per matching row.
+    pub fn query_datoms(&self, patterns: &[Pattern], limit: Option<u32>) -> Vec<HashMap<&'static str, Value>> {
+        let (mut sql, params, bindings) = compile(patterns);
+
+        assert!(
+            params.len() <= Self::MAX_BOUND_PARAMETERS,
+            "Datom query has too many bound terms ({}) for a single statement",
+            params.len()
+        );
+
+        if let Some(limit) = limit {
+            sql += &format!(" LIMIT {}", limit);
+        }
+
+        self.with_conn(|conn| {
+            let mut stmnt = conn.prepare(&sql).expect("Could not prepare datom query");
+
+            let param_refs: Vec<&ToSql> = params.iter().map(|p| p.as_ref()).collect();
+            let rows = stmnt.query_map(&param_refs, |row| {
+                let mut result = HashMap::with_capacity(bindings.len());
+                for binding in &bindings {
+                    let value = match binding.role {
+                        Role::Entity => Value::Int(row.get(binding.var)),
+                        Role::Attribute => Value::Text(row.get(binding.var)),
+                        Role::Val => {
+                            let tag: String = row.get(format!("{}__type", binding.var).as_str());
+                            let column = format!("{}__value", binding.var);
+                            match tag.as_str() {
+                                "text" => Value::Text(row.get(column.as_str())),
+                                "int" => Value::Int(row.get(column.as_str())),
+                                "real" => Value::Real(row.get(column.as_str())),
+                                "bool" => Value::Bool(row.get(column.as_str())),
+                                other => panic!("Unknown datom value_type {:?}", other),
+                            }
+                        }
+                    };
+                    result.insert(binding.var, value);
+                }
+                result
+            }).expect("Could not bind params");
+
+            rows.map(|row| row.expect("Could not construct")).collect()
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Pattern, Term, Value};
+    use crate::data::Rusqlite;
+
+    #[test]
+    fn test_pattern_query_joins_on_shared_variables() {
+        let mut repo = Rusqlite::in_memory();
+        repo.setup_datoms().expect("Could not setup datoms");
+
+        repo.assert(1, "user/name", Value::from("Mike"));
+        repo.assert(1, "user/task", Value::from(2));
+        repo.assert(2, "task/done", Value::from(true));
+
+        let results = repo.query_datoms(&[
+            Pattern::new(Term::var("?u"), Term::val("user/name"), Term::val("Mike")),
+            Pattern::new(Term::var("?u"), Term::val("user/task"), Term::var("?t")),
+            Pattern::new(Term::var("?t"), Term::val("task/done"), Term::val(true)),
+        ], None);
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].get("?u"), Some(&Value::Int(1)));
+        assert_eq!(results[0].get("?t"), Some(&Value::Int(2)));
+    }
+}
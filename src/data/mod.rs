@@ -1,30 +1,145 @@
 use crate::domain::entities::{User, Task};
 
-use rusqlite::{Connection, NO_PARAMS, types::ToSql};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH, Duration};
+
+use rusqlite::{NO_PARAMS, types::ToSql, Connection};
+use r2d2::{Pool, PooledConnection};
+use r2d2_sqlite::SqliteConnectionManager;
+
+pub mod eav;
 
 pub trait SQLable {
     fn select() -> &'static str;
     fn insert() -> &'static str;
     fn create_table() -> &'static str;
 
+    // The table this type's rows live in, surfaced in a `TxReport` so a
+    // change-observer can tell what just got written.
+    fn table_name() -> &'static str;
+
+    // Child column -> parent table, e.g. `("user_id", "users")` for `Task`.
+    // Declarative for now; a real migration runner would use it to order
+    // `setup::<T>()` calls so parents land before their children.
+    fn foreign_keys() -> &'static [(&'static str, &'static str)] { &[] }
+
+    // The column `get`/`find` should filter on for a single id. Plain "id"
+    // is ambiguous once `select()` brings in a join (see `User`), so that
+    // case has to qualify it.
+    fn id_column() -> &'static str { "id" }
+
+    // How many `?` placeholders a single row of this type needs, so a bulk
+    // insert can be chunked to stay under SQLite's bound-parameter limit.
+    fn bindings_per_row() -> usize;
+
     #[inline(always)]
-    fn bind<F, T>(data: &Self, consumer: F) -> T where F: FnMut(&'static str, &[&ToSql]) -> T;
+    fn bind<F, T>(data: &Self, repo: &Rusqlite, consumer: F) -> T where F: FnMut(&'static str, &[&ToSql]) -> T;
+
+    // Owned version of the bindings `bind` would pass to its consumer, used
+    // to build a single multi-row INSERT instead of one statement per row.
+    // `parent_id` is `Some` when these rows are being saved as children of
+    // another entity (e.g. a `User`'s tasks) and need their foreign key set.
+    fn to_bound_values(data: &Self, parent_id: Option<i64>) -> Vec<Box<ToSql>>;
+
+    // `INSERT INTO ... VALUES (?,?,?),(?,?,?),...` for `row_count` rows.
+    fn insert_bulk(row_count: usize) -> String;
+
+    // Called once per row right after a bulk insert assigns it `id`, so an
+    // aggregate with children (e.g. a `User`'s tasks) gets them saved too.
+    // `bind`'s consumer closure lets the singular `save` path do this inline;
+    // a bulk `INSERT ... VALUES` has no per-row hook to thread that through,
+    // hence this separate method. Default is a no-op for childless types.
+    fn save_children(_repo: &Rusqlite, _data: &Self, _id: i64) {}
+
+    // The id of the leading row, read before `from_row` gets a chance to
+    // throw it away; `query` needs it to group joined rows by parent.
+    fn row_id<'row, 'stmt>(row: &rusqlite::Row<'row, 'stmt>) -> i64 { row.get(0) }
 
     fn from_row<'row, 'stmt>(repo: &Rusqlite, row: &rusqlite::Row<'row, 'stmt>) -> Self;
+
+    // Collapse the (id, Self) pairs `query` collected into the final result.
+    // Plain tables just drop the id; `User` folds repeated ids (one per
+    // joined task row) into a single user with all of its tasks.
+    fn merge_joined(rows: Vec<(i64, Self)>) -> Vec<Self> where Self: Sized {
+        rows.into_iter().map(|(_, value)| value).collect()
+    }
+}
+
+// Comparison operators a `Predicate::Cmp` leaf can render.
+#[derive(Debug)]
+pub enum Op { Eq, Ne, Lt, Le, Gt, Ge, Like }
+
+impl Op {
+    fn as_sql(&self) -> &'static str {
+        match self {
+            Op::Eq => "=",
+            Op::Ne => "!=",
+            Op::Lt => "<",
+            Op::Le => "<=",
+            Op::Gt => ">",
+            Op::Ge => ">=",
+            Op::Like => "LIKE",
+        }
+    }
 }
 
-pub struct QueryValue<'query>(&'static str, &'query ToSql);
+// A small predicate tree, so `query` isn't stuck emitting `field = (?)`
+// joined by `AND`. `Cmp` is a leaf comparison; `And`/`Or` combine subtrees.
+pub enum Predicate {
+    Cmp(&'static str, Op, Box<ToSql>),
+    And(Vec<Predicate>),
+    Or(Vec<Predicate>),
+}
+
+// Renders `pred` as parenthesized SQL onto `sql`, pushing its bound values
+// onto `params` in the same left-to-right order so placeholders line up.
+fn render_predicate<'pred>(pred: &'pred Predicate, sql: &mut String, params: &mut Vec<&'pred ToSql>) {
+    match pred {
+        Predicate::Cmp(field, op, value) => {
+            sql.push_str(field);
+            sql.push(' ');
+            sql.push_str(op.as_sql());
+            sql.push_str(" (?)");
+            params.push(value.as_ref());
+        }
+        Predicate::And(preds) => render_group(preds, "AND", "1", sql, params),
+        Predicate::Or(preds) => render_group(preds, "OR", "0", sql, params),
+    }
+}
+
+// An empty `And` is vacuously true, an empty `Or` vacuously false - render
+// those as `1`/`0` rather than the invalid `()`.
+fn render_group<'pred>(preds: &'pred [Predicate], joiner: &str, empty: &str, sql: &mut String, params: &mut Vec<&'pred ToSql>) {
+    if preds.is_empty() {
+        sql.push_str(empty);
+        return;
+    }
+
+    sql.push('(');
+    for (i, pred) in preds.iter().enumerate() {
+        if i > 0 {
+            sql.push(' ');
+            sql.push_str(joiner);
+            sql.push(' ');
+        }
+        render_predicate(pred, sql, params);
+    }
+    sql.push(')');
+}
 
 pub trait SQLSearchable : Searchable + SQLable {
-    fn build_query(creds: &[<Self as Searchable>::Credentials]) -> Vec<QueryValue>;
+    fn build_query(creds: &[<Self as Searchable>::Credentials]) -> Predicate;
 }
 
-// Some kind of generalization so I can extract the things that differ.
-// The major drawback I found with this, is the problems related to the relation
-// between different objects. A User has Tasks, but I don't get them like this
-// and a LEFT JOIN doesn't really help, because then we need to post-process the data
+// A User owns its Tasks through a real `tasks.user_id` foreign key now, and
+// `select()` below pulls both sides in one LEFT JOIN; `merge_joined` folds
+// the resulting (one row per task) duplicates back into one User each.
 impl SQLable for User {
-    fn select() -> &'static str { "SELECT * FROM users" }
+    fn select() -> &'static str {
+        "SELECT users.id, users.name, tasks.id, tasks.desc, tasks.done, tasks.tags, tasks.due \
+         FROM users LEFT JOIN tasks ON tasks.user_id = users.id"
+    }
     fn insert() -> &'static str { "INSERT INTO users (name) VALUES (?)" }
     fn create_table() -> &'static str {
         "CREATE TABLE users (
@@ -33,141 +148,495 @@ impl SQLable for User {
         )"
     }
 
-    fn bind<F, T>(data: &Self, mut consumer: F) -> T where F: FnMut(&'static str, &[&ToSql]) -> T {
+    fn table_name() -> &'static str { "users" }
+
+    fn id_column() -> &'static str { "users.id" }
+
+    fn bindings_per_row() -> usize { 1 }
+
+    fn bind<F, T>(data: &Self, repo: &Rusqlite, mut consumer: F) -> T where F: FnMut(&'static str, &[&ToSql]) -> T {
         let bindings: [&ToSql; 1] = [&data.name];
         let my_id = consumer(Self::insert(), &bindings);
 
-        // TODO: optimize with bulk insert
-        for task in data.tasks() {
-            Task::bind(task, &mut consumer);
+        if !data.tasks().is_empty() {
+            repo.save_all_owned_by(data.tasks(), Some(my_id));
         }
 
         my_id
     }
 
-    fn from_row<'row, 'stmt>(repo: &Rusqlite, row: &rusqlite::Row<'row, 'stmt>) -> Self {
-        let id: <Rusqlite as Repository<User>>::Id = row.get(0);
+    fn to_bound_values(data: &Self, _parent_id: Option<i64>) -> Vec<Box<ToSql>> {
+        vec![Box::new(data.name.clone())]
+    }
+
+    fn insert_bulk(row_count: usize) -> String {
+        let values = vec!["(?)"; row_count].join(",");
+        format!("INSERT INTO users (name) VALUES {}", values)
+    }
+
+    // `to_bound_values` only carries a `User`'s own column (`name`) - a bulk
+    // insert has no room to also stash its tasks, so they're saved here
+    // instead, once this row's id is known. Mirrors what `bind` does inline
+    // for the singular `save` path.
+    fn save_children(repo: &Rusqlite, data: &Self, id: i64) {
+        if !data.tasks().is_empty() {
+            repo.save_all_owned_by(data.tasks(), Some(id));
+        }
+    }
+
+    fn from_row<'row, 'stmt>(_repo: &Rusqlite, row: &rusqlite::Row<'row, 'stmt>) -> Self {
         let name: String = row.get(1);
-        let tasks = repo.query(&[QueryValue("id", &id)], None);
+
+        let task_id: Option<i64> = row.get(2);
+        let tasks = match task_id {
+            Some(_) => {
+                let desc: String = row.get(3);
+                let done: bool = row.get(4);
+                let tags: Option<String> = row.get(5);
+                let due: Option<i64> = row.get(6);
+                let tag_vec = tags.map_or(vec![], |s| s.split(",").map(Into::into).collect());
+                vec![Task { desc: desc, done: done, tags: tag_vec, due: due_from_epoch_secs(due) }]
+            }
+            None => vec![],
+        };
+
         User::with_tasks(name, tasks)
     }
-}
 
-impl SQLSearchable for User {
-    fn build_query(creds: &[<Self as Searchable>::Credentials]) -> Vec<QueryValue> {
-        let mut result = Vec::with_capacity(creds.len());
-        for pred in creds {
-            match pred {
-                UserSearchTerms::Name(ref name) => {
-                    result.push(QueryValue("name", name));
+    fn merge_joined(rows: Vec<(i64, Self)>) -> Vec<Self> {
+        let mut merged: Vec<(i64, Self)> = Vec::with_capacity(rows.len());
+        for (id, user) in rows {
+            match merged.iter_mut().find(|(existing_id, _)| *existing_id == id) {
+                Some((_, existing)) => {
+                    for task in user.tasks() {
+                        existing.add_task(task.clone());
+                    }
                 }
+                None => merged.push((id, user)),
             }
         }
-        result
+        merged.into_iter().map(|(_, user)| user).collect()
+    }
+}
+
+impl SQLSearchable for User {
+    fn build_query(creds: &[<Self as Searchable>::Credentials]) -> Predicate {
+        let clauses = creds.iter().map(|pred| match pred {
+            UserSearchTerms::Name(name) => Predicate::Cmp("name", Op::Eq, Box::new(name.clone())),
+            UserSearchTerms::NameLike(fragment) => Predicate::Cmp("name", Op::Like, Box::new(format!("%{}%", fragment))),
+        }).collect();
+        Predicate::And(clauses)
     }
 }
 
+// `due` rides along as seconds-since-epoch, since `SystemTime` itself has no
+// stable on-disk representation and SQLite has no native timestamp type.
+fn due_to_epoch_secs(due: Option<SystemTime>) -> Option<i64> {
+    due.map(|t| t.duration_since(UNIX_EPOCH).expect("Task due before the epoch").as_secs() as i64)
+}
+
+fn due_from_epoch_secs(secs: Option<i64>) -> Option<SystemTime> {
+    secs.map(|s| UNIX_EPOCH + Duration::from_secs(s as u64))
+}
+
 impl SQLable for Task {
     fn select() -> &'static str { "SELECT * from tasks" }
-    fn insert() -> &'static str { "INSERT INTO tasks (desc, done, tags) VALUES (?, ?, ?)" }
+    fn insert() -> &'static str { "INSERT INTO tasks (desc, done, tags, due, user_id) VALUES (?, ?, ?, ?, ?)" }
     fn create_table() -> &'static str {
         "CREATE TABLE tasks (
             id         INTEGER PRIMARY KEY,
             desc       TEXT NOT NULL,
             done       BOOL NOT NULL,
-            tags       TEXT
+            tags       TEXT,
+            due        INTEGER,
+            user_id    INTEGER REFERENCES users(id)
         )"
     }
 
-    fn bind<F, T>(data: &Self, mut consumer: F) -> T where F: FnMut(&'static str, &[&ToSql]) -> T {
+    fn table_name() -> &'static str { "tasks" }
+
+    fn foreign_keys() -> &'static [(&'static str, &'static str)] { &[("user_id", "users")] }
+
+    fn bindings_per_row() -> usize { 5 }
+
+    fn bind<F, T>(data: &Self, _repo: &Rusqlite, mut consumer: F) -> T where F: FnMut(&'static str, &[&ToSql]) -> T {
         let joined = data.tags.join(",");
         let tags: &ToSql = if joined.len() > 0 { &joined } else { &rusqlite::types::Null };
-        let bindings: [&ToSql; 3] = [&data.desc, &data.done, &tags];
+        let due = due_to_epoch_secs(data.due);
+        // Saved standalone (not through `User::bind`), so there's no parent to link.
+        let bindings: [&ToSql; 5] = [&data.desc, &data.done, &tags, &due, &rusqlite::types::Null];
         let id = consumer(Self::insert(), &bindings);
         id
     }
 
+    fn to_bound_values(data: &Self, parent_id: Option<i64>) -> Vec<Box<ToSql>> {
+        let tags: Option<String> = if data.tags.is_empty() { None } else { Some(data.tags.join(",")) };
+        vec![
+            Box::new(data.desc.clone()),
+            Box::new(data.done),
+            Box::new(tags),
+            Box::new(due_to_epoch_secs(data.due)),
+            Box::new(parent_id),
+        ]
+    }
+
+    fn insert_bulk(row_count: usize) -> String {
+        let values = vec!["(?, ?, ?, ?, ?)"; row_count].join(",");
+        format!("INSERT INTO tasks (desc, done, tags, due, user_id) VALUES {}", values)
+    }
+
     fn from_row<'row, 'stmt>(_repo: &Rusqlite, row: &rusqlite::Row<'row, 'stmt>) -> Self {
         let desc: String = row.get("desc");
         let done: bool = row.get("done");
         let tags: Option<String> = row.get("tags");
+        let due: Option<i64> = row.get("due");
 
         let tag_vec = tags.map_or(vec![], |s| s.split(",").map(Into::into).collect());
         Task {
             desc: desc,
             done: done,
             tags: tag_vec,
-            due: None, // No support here yet, lawl
+            due: due_from_epoch_secs(due),
         }
     }
 }
 
-// The thing that ties this imlpementation to rusqlite
+impl SQLSearchable for Task {
+    fn build_query(creds: &[<Self as Searchable>::Credentials]) -> Predicate {
+        let clauses = creds.iter().map(|pred| match pred {
+            TaskSearchTerms::DescContains(fragment) => Predicate::Cmp("desc", Op::Like, Box::new(format!("%{}%", fragment))),
+            TaskSearchTerms::DueBefore(when) => Predicate::Cmp("due", Op::Le, Box::new(due_to_epoch_secs(Some(*when)))),
+        }).collect();
+        Predicate::And(clauses)
+    }
+}
+
+// What a successful write made happen, handed to every observer subscribed
+// to `table`. `ids` are the newly assigned rowids, in insertion order.
+pub struct TxReport {
+    pub table: &'static str,
+    pub ids: Vec<i64>,
+}
+
+// The thing that ties this imlpementation to rusqlite.
+//
+// Backed by an r2d2 pool instead of a lone `Connection`, so readers can run
+// concurrently - SQLite itself only tolerates one writer at a time though,
+// so every write-shaped operation takes `write_lock` before touching the
+// database. `transaction` takes it once and hands out `&Rusqlite` so the
+// closure's `save_unlocked`/`save_all_unlocked` calls don't try to take it
+// again; `with_conn` is what actually makes that safe, pinning every query
+// the closure runs to the one connection `transaction` opened.
 pub struct Rusqlite {
-    conn: rusqlite::Connection,
+    pool: Pool<SqliteConnectionManager>,
+    write_lock: Mutex<()>,
+
+    // Keyed by `SQLable::table_name()`. Registration (`observe`) happens
+    // through `&mut self`, but dispatch happens from the lock-free `&self`
+    // write paths, so this still needs interior mutability. `Send + Sync`
+    // on the boxed closure matters here: without it, an observer carrying
+    // a non-`Sync` capture would make this whole `Mutex` (and so `Rusqlite`
+    // itself) `!Sync`, defeating the point of sharing a repo across threads.
+    observers: Mutex<HashMap<&'static str, Vec<Box<Fn(&TxReport) + Send + Sync>>>>,
+
+    // `None` outside of a `transaction`, meaning a write's report dispatches
+    // as soon as it's produced (a lone `save` autocommits immediately). Set
+    // to `Some(buffer)` for the lifetime of a `transaction` closure, so its
+    // reports only reach observers after the `COMMIT` actually succeeds.
+    pending: Mutex<Option<Vec<TxReport>>>,
+
+    // The connection `transaction` is running `BEGIN`/`COMMIT` on, for the
+    // lifetime of its closure. `None` outside of a transaction, in which
+    // case `with_conn` just checks a connection out of the pool per call, as
+    // before. Pinning this one connection is what makes a transaction's
+    // writes actually atomic - without it, `conn()` would hand the closure
+    // a different pooled connection per call, each autocommitting on its own.
+    tx_conn: Mutex<Option<PooledConnection<SqliteConnectionManager>>>,
 }
 
-use crate::domain::entities::UserSearchTerms;
+use crate::domain::entities::{UserSearchTerms, TaskSearchTerms};
 
 impl Rusqlite {
     pub fn in_memory() -> Self {
-        Rusqlite {
-            conn: Connection::open_in_memory().expect("Could not open Database"),
+        let manager = SqliteConnectionManager::memory();
+        // Pooled connections each get their own private `:memory:` database,
+        // so stick to a single reused connection to keep them all looking
+        // at the same data.
+        let pool = Pool::builder().max_size(1).build(manager).expect("Could not build pool");
+        Rusqlite { pool, write_lock: Mutex::new(()), observers: Mutex::new(HashMap::new()), pending: Mutex::new(None), tx_conn: Mutex::new(None) }
+    }
+
+    pub fn open<P: AsRef<std::path::Path>>(path: P) -> Self {
+        let manager = SqliteConnectionManager::file(path).with_init(|conn| {
+            conn.execute_batch("PRAGMA journal_mode = WAL; PRAGMA synchronous = NORMAL;")
+        });
+        let pool = Pool::new(manager).expect("Could not build pool");
+        Rusqlite { pool, write_lock: Mutex::new(()), observers: Mutex::new(HashMap::new()), pending: Mutex::new(None), tx_conn: Mutex::new(None) }
+    }
+
+    fn conn(&self) -> PooledConnection<SqliteConnectionManager> {
+        self.pool.get().expect("Could not get a pooled connection")
+    }
+
+    // Every query-running method goes through here instead of calling
+    // `conn()` directly, so that inside a `transaction` they all land on the
+    // one connection it pinned in `tx_conn` - otherwise each would check out
+    // a separate pooled connection and autocommit independently, same as a
+    // lone `save` would.
+    fn with_conn<R>(&self, f: impl FnOnce(&Connection) -> R) -> R {
+        let tx_conn = self.tx_conn.lock().expect("Transaction connection lock poisoned");
+        match &*tx_conn {
+            Some(conn) => f(conn),
+            None => {
+                drop(tx_conn);
+                f(&self.conn())
+            }
         }
     }
 
     pub fn setup<T: SQLable>(&self) -> Result<usize, rusqlite::Error> {
-        self.conn.execute(T::create_table(), NO_PARAMS)
+        self.with_conn(|conn| conn.execute(T::create_table(), NO_PARAMS))
+    }
+
+    // Subscribes `f` to every future write of `T`'s table. Takes `&mut self`
+    // since registration isn't meant to race with itself; dispatch from the
+    // lock-free write paths still goes through the `Mutex`.
+    pub fn observe<T: SQLable>(&mut self, f: impl Fn(&TxReport) + Send + Sync + 'static) {
+        self.observers.get_mut().expect("Observers lock poisoned")
+            .entry(T::table_name())
+            .or_insert_with(Vec::new)
+            .push(Box::new(f));
+    }
+
+    // Fires `report` to every observer registered for its table. Called
+    // either right after an autocommitted write, or once per buffered
+    // report after a `transaction` commits.
+    fn dispatch(&self, report: &TxReport) {
+        let observers = self.observers.lock().expect("Observers lock poisoned");
+        if let Some(subscribers) = observers.get(&report.table) {
+            for observer in subscribers {
+                observer(report);
+            }
+        }
+    }
+
+    // Reports a write of `ids` to `table`: buffered if we're inside a
+    // `transaction`, dispatched immediately otherwise (a lone `save` is its
+    // own implicit commit).
+    fn record_write(&self, table: &'static str, ids: Vec<i64>) {
+        if ids.is_empty() {
+            return;
+        }
+        let report = TxReport { table: table, ids: ids };
+
+        let mut pending = self.pending.lock().expect("Pending reports lock poisoned");
+        match pending.as_mut() {
+            Some(buffered) => buffered.push(report),
+            None => {
+                drop(pending);
+                self.dispatch(&report);
+            }
+        }
+    }
+
+    // Runs `f` inside `BEGIN`/`COMMIT`, rolling back on error, with the
+    // write lock held for the whole closure so a multi-entity save (e.g. a
+    // `User` plus its tasks) can't be observed half-written. Observers only
+    // hear about the writes `f` made once this `COMMIT` actually succeeds.
+    //
+    // `BEGIN` runs on one connection checked out here, and that exact
+    // connection is pinned in `tx_conn` for `with_conn` to hand back to
+    // every query `f` runs, all the way down through `COMMIT`/`ROLLBACK` -
+    // a pool can otherwise serve a different connection per call, in which
+    // case each of `f`'s writes would autocommit on its own and `ROLLBACK`
+    // would have nothing left to undo.
+    pub fn transaction<F, R>(&self, f: F) -> Result<R, rusqlite::Error>
+    where F: FnOnce(&Rusqlite) -> Result<R, rusqlite::Error> {
+        let _write_guard = self.write_lock.lock().expect("Write lock poisoned");
+
+        {
+            let mut pending = self.pending.lock().expect("Pending reports lock poisoned");
+            assert!(pending.is_none(), "Entered a transaction while a previous one's reports were still pending");
+            *pending = Some(Vec::new());
+        }
+
+        let conn = self.conn();
+        if let Err(err) = conn.execute_batch("BEGIN") {
+            *self.pending.lock().expect("Pending reports lock poisoned") = None;
+            return Err(err);
+        }
+        *self.tx_conn.lock().expect("Transaction connection lock poisoned") = Some(conn);
+
+        let result = f(self);
+
+        let conn = self.tx_conn.lock().expect("Transaction connection lock poisoned")
+            .take().expect("Transaction connection missing after closure ran");
+
+        // Whichever way this goes, no buffered reports must survive past this
+        // point - a commit failure leaving `pending` stuck as `Some` would
+        // make every later non-transactional `save` buffer forever instead
+        // of dispatching.
+        let outcome = match result {
+            Ok(value) => match conn.execute_batch("COMMIT") {
+                Ok(()) => {
+                    let reports = self.pending.lock().expect("Pending reports lock poisoned")
+                        .take().expect("Pending reports missing after commit");
+                    for report in &reports {
+                        self.dispatch(report);
+                    }
+                    Ok(value)
+                }
+                Err(err) => Err(err),
+            },
+            Err(err) => {
+                conn.execute_batch("ROLLBACK").ok();
+                Err(err)
+            }
+        };
+
+        *self.pending.lock().expect("Pending reports lock poisoned") = None;
+        outcome
     }
 
     fn get_all<T: SQLable>(&self) -> Vec<T> {
-        self.query(&[], None)
+        self.query(None, None)
     }
 
     fn get<T: SQLable>(&self, id: &i64) -> Option<T> {
-        let result = self.query(&[QueryValue("id", id)], Some(1));
+        let predicate = Predicate::Cmp(T::id_column(), Op::Eq, Box::new(*id));
+        let result = self.query(Some(&predicate), Some(1));
         result.into_iter().next()
     }
 
-    fn save<T: SQLable>(&mut self, data: &T) -> i64 {
-        T::bind(data, |sql, bindings| {
-            let mut stmnt = self.conn.prepare(sql).expect("Could not prepare save");
-            stmnt.insert(bindings).expect("Could not insert")
-        })
+    // Does not take `write_lock` itself - callers (the `Repository` impl,
+    // or a `transaction` closure that already holds it) are responsible for
+    // serializing writes. Named `_unlocked` rather than plain `save` so it
+    // can't be picked up by accident in place of `Repository::save` - an
+    // inherent method of the same name would shadow the trait's on any
+    // caller holding a concrete `Rusqlite`, silently skipping the lock.
+    fn save_unlocked<T: SQLable>(&self, data: &T) -> i64 {
+        let id = T::bind(data, self, |sql, bindings| {
+            self.with_conn(|conn| {
+                let mut stmnt = conn.prepare(sql).expect("Could not prepare save");
+                stmnt.insert(bindings).expect("Could not insert")
+            })
+        });
+        self.record_write(T::table_name(), vec![id]);
+        id
     }
 
-    fn query<T: SQLable>(&self, parameters: &[QueryValue], limit: Option<u32>) -> Vec<T> {
-        let mut sql = T::select().to_string();
+    // SQLite caps the number of bound parameters per statement (999 on old
+    // builds, 32766 on builds compiled with the larger SQLITE_MAX_VARIABLE_NUMBER),
+    // so stay conservative and chunk rows to fit under the smaller limit.
+    const MAX_BOUND_PARAMETERS: usize = 999;
 
-        if parameters.len() > 0 {
-            sql += " WHERE";
-        }
+    fn save_all_unlocked<T: SQLable>(&self, data: &[T]) -> Vec<i64> {
+        self.save_all_owned_by(data, None)
+    }
 
-        let mut params: Vec<&ToSql> = Vec::with_capacity(parameters.len());
-        let mut iter = parameters.iter().peekable();
+    // SQLite only grew `RETURNING` in 3.35 (2021); older builds reject the
+    // clause outright, so check the connection's version at the start of
+    // each bulk insert rather than assuming it's there and blowing up every
+    // bulk insert on an older libsqlite3. The result is not cached.
+    fn supports_returning(conn: &Connection) -> bool {
+        let version: String = conn.query_row("SELECT sqlite_version()", NO_PARAMS, |row| row.get(0))
+            .expect("Could not read sqlite_version()");
+        let mut segments = version.split('.').filter_map(|s| s.parse::<u32>().ok());
+        let major = segments.next().unwrap_or(0);
+        let minor = segments.next().unwrap_or(0);
+        (major, minor) >= (3, 35)
+    }
 
-        while let Some(QueryValue(field, value)) = iter.next() {
-            sql += &format!(" {} = (?)", field);
-            params.push(value);
+    // Bulk-insert `data` as children of `parent_id` (if any), setting each
+    // row's foreign key accordingly. Used directly by `save_all_unlocked`,
+    // and by `User::bind`/`User::save_children` to stamp a user's own id
+    // onto its tasks. Like `save_unlocked`, assumes the caller already
+    // holds `write_lock`.
+    fn save_all_owned_by<T: SQLable>(&self, data: &[T], parent_id: Option<i64>) -> Vec<i64> {
+        if data.is_empty() {
+            return Vec::new();
+        }
 
-            if iter.peek().is_some() {
-                sql += " AND";
+        let rows_per_chunk = (Self::MAX_BOUND_PARAMETERS / T::bindings_per_row()).max(1);
+
+        let ids = self.with_conn(|conn| {
+            let use_returning = Self::supports_returning(conn);
+            let mut ids = Vec::with_capacity(data.len());
+            for chunk in data.chunks(rows_per_chunk) {
+                let owned: Vec<Vec<Box<ToSql>>> = chunk.iter().map(|row| T::to_bound_values(row, parent_id)).collect();
+                let params: Vec<&ToSql> = owned.iter()
+                    .flat_map(|row| row.iter().map(|v| v.as_ref()))
+                    .collect();
+
+                if use_returning {
+                    // Sidesteps the contiguous-rowid assumption the
+                    // `last_insert_rowid()` fallback below makes - but
+                    // SQLite doesn't guarantee `RETURNING` rows come back in
+                    // VALUES order, so sort rather than zip them against
+                    // `chunk` positionally. Ids are still handed out
+                    // monotonically as each row is processed, so sorting
+                    // recovers the right order regardless of how the result
+                    // set itself is ordered.
+                    let sql = format!("{} RETURNING id", T::insert_bulk(chunk.len()));
+                    let mut stmnt = conn.prepare(&sql).expect("Could not prepare bulk insert");
+                    let mut returned: Vec<i64> = stmnt.query_map(&params, |row| row.get(0))
+                        .expect("Could not bind params")
+                        .map(|id| id.expect("Could not read returned id"))
+                        .collect();
+                    returned.sort();
+                    ids.extend(returned);
+                } else {
+                    let sql = T::insert_bulk(chunk.len());
+                    let mut stmnt = conn.prepare(&sql).expect("Could not prepare bulk insert");
+                    stmnt.execute(&params).expect("Could not bulk insert");
+
+                    // Rowids are assigned sequentially within a single INSERT, so we
+                    // can recover the whole chunk's ids from the last one.
+                    let last_id = conn.last_insert_rowid();
+                    let first_id = last_id - (chunk.len() as i64 - 1);
+                    ids.extend(first_id..=last_id);
+                }
             }
+            ids
+        });
+
+        for (row, &id) in data.iter().zip(ids.iter()) {
+            T::save_children(self, row, id);
         }
 
-        if let Some(limit) = limit {
-            sql += &format!(" LIMIT {}", limit);
+        self.record_write(T::table_name(), ids.clone());
+        ids
+    }
+
+    fn query<T: SQLable>(&self, predicate: Option<&Predicate>, limit: Option<u32>) -> Vec<T> {
+        let mut sql = T::select().to_string();
+        let mut params: Vec<&ToSql> = Vec::new();
+
+        if let Some(predicate) = predicate {
+            sql += " WHERE ";
+            render_predicate(predicate, &mut sql, &mut params);
         }
 
-        let mut stmnt = self.conn
-            .prepare(&sql)
-            .expect("Could not prepare statement");
+        let rows: Vec<(i64, T)> = self.with_conn(|conn| {
+            let mut stmnt = conn
+                .prepare(&sql)
+                .expect("Could not prepare statement");
+
+            let iter = stmnt
+                .query_map(&params, |row| (T::row_id(row), T::from_row(&self, row)))
+                .expect("Could not bind params");
 
-        let iter = stmnt
-            .query_map(&params, |row| T::from_row(&self, row))
-            .expect("Could not bind params");
+            iter.map(|elem| elem.expect("Could not construct")).collect()
+        });
 
-        iter.map(|elem| elem.expect("Could not construct")).collect()
+        // Limiting has to happen after `merge_joined`, not as a SQL LIMIT:
+        // a join can emit several rows per entity (one per task), and a SQL
+        // LIMIT would cut a user off mid-task instead of after N users.
+        let mut merged = T::merge_joined(rows);
+        if let Some(limit) = limit {
+            merged.truncate(limit as usize);
+        }
+        merged
     }
 }
 
@@ -186,7 +655,13 @@ impl<T: SQLable> Repository<T> for Rusqlite {
     }
 
     fn save(&mut self, data: &T) -> Self::Id {
-        self.save::<T>(data)
+        let _write_guard = self.write_lock.lock().expect("Write lock poisoned");
+        Rusqlite::save_unlocked::<T>(self, data)
+    }
+
+    fn save_all(&mut self, data: &[T]) -> Vec<Self::Id> {
+        let _write_guard = self.write_lock.lock().expect("Write lock poisoned");
+        Rusqlite::save_all_unlocked::<T>(self, data)
     }
 }
 
@@ -194,7 +669,7 @@ use crate::domain::{Searchable, SearchableRepository};
 impl<T: SQLSearchable> SearchableRepository<T> for Rusqlite {
     fn find(&self, credentials: &[<T as Searchable>::Credentials], limit: Option<u32>) -> Vec<T> {
         let query_data = T::build_query(credentials);
-        self.query(&query_data, limit)
+        self.query(Some(&query_data), limit)
     }
 }
 
@@ -227,7 +702,6 @@ impl<T: Clone> Repository<T> for TrivialRepository<T> {
 
 }
 
-use std::collections::HashMap;
 use uuid::Uuid;
 
 pub struct HashRepository<T: Clone>(HashMap<Uuid, T>);
@@ -334,4 +808,125 @@ mod test {
 
 
     }
+
+    #[test]
+    fn save_all_bulk_inserts_and_keeps_child_tasks() {
+        use super::Rusqlite;
+        use super::{User, Task};
+        use crate::domain::Repository;
+
+        let mut repo = Rusqlite::in_memory();
+        repo.setup::<User>().expect("Could not setup tables");
+        repo.setup::<Task>().expect("Could not setup tables");
+
+        let a = User::with_tasks("A", vec![Task::new("A's task")]);
+        let b = User::with_tasks("B", vec![]);
+        let c = User::with_tasks("C", vec![Task::new("C's task")]);
+
+        let ids = repo.save_all(&[a.clone(), b.clone(), c.clone()]);
+        assert_eq!(ids.len(), 3);
+
+        let expected = [&a, &b, &c];
+        for (id, expected) in ids.iter().zip(expected.iter()) {
+            let fetched: User = repo.get(id).expect("No such user");
+            assert_eq!(&fetched, *expected);
+        }
+    }
+
+    #[test]
+    fn task_due_survives_save_and_get_roundtrip() {
+        use super::Rusqlite;
+        use super::Task;
+        use crate::domain::Repository;
+        use std::time::{UNIX_EPOCH, Duration};
+
+        let mut repo = Rusqlite::in_memory();
+        repo.setup::<Task>().expect("Could not setup tables");
+
+        let due = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let task = Task::new("Buy milk").due(due);
+        let id = repo.save(&task);
+
+        let fetched: Task = repo.get(&id).expect("No such task");
+        assert_eq!(fetched.due, Some(due));
+    }
+
+    #[test]
+    fn due_before_filters_tasks_by_due_date() {
+        use super::Rusqlite;
+        use super::{Task, TaskSearchTerms};
+        use crate::domain::{Repository, SearchableRepository};
+        use std::time::{UNIX_EPOCH, Duration};
+
+        let mut repo = Rusqlite::in_memory();
+        repo.setup::<Task>().expect("Could not setup tables");
+
+        let earlier = Task::new("Earlier").due(UNIX_EPOCH + Duration::from_secs(1_000_000));
+        let later = Task::new("Later").due(UNIX_EPOCH + Duration::from_secs(2_000_000));
+
+        repo.save(&earlier);
+        repo.save(&later);
+
+        let found: Vec<Task> = repo.find(
+            &[TaskSearchTerms::DueBefore(UNIX_EPOCH + Duration::from_secs(1_500_000))],
+            None);
+
+        assert_eq!(found, vec![earlier]);
+    }
+
+    #[test]
+    fn like_predicates_match_substrings() {
+        use super::Rusqlite;
+        use super::{User, Task, UserSearchTerms, TaskSearchTerms};
+        use crate::domain::{Repository, SearchableRepository};
+
+        let mut repo = Rusqlite::in_memory();
+        repo.setup::<User>().expect("Could not setup tables");
+        repo.setup::<Task>().expect("Could not setup tables");
+
+        repo.save(&User::new("Alice"));
+        repo.save(&User::new("Bob"));
+
+        let users: Vec<User> = repo.find(&[UserSearchTerms::NameLike("li".to_string())], None);
+        assert_eq!(users, vec![User::new("Alice")]);
+
+        repo.save(&Task::new("Buy milk"));
+        repo.save(&Task::new("Walk the dog"));
+
+        let tasks: Vec<Task> = repo.find(&[TaskSearchTerms::DescContains("milk".to_string())], None);
+        assert_eq!(tasks, vec![Task::new("Buy milk")]);
+    }
+
+    #[test]
+    fn observers_fire_on_commit_not_on_rollback() {
+        use super::Rusqlite;
+        use super::User;
+        use crate::domain::Repository;
+        use std::sync::{Arc, Mutex};
+
+        let mut repo = Rusqlite::in_memory();
+        repo.setup::<User>().expect("Could not setup tables");
+
+        let seen_ids: Arc<Mutex<Vec<i64>>> = Arc::new(Mutex::new(Vec::new()));
+        let seen_ids_handle = seen_ids.clone();
+        repo.observe::<User>(move |report| {
+            seen_ids_handle.lock().unwrap().extend(report.ids.iter().cloned());
+        });
+
+        repo.save(&User::new("Committed via a lone save"));
+        assert_eq!(*seen_ids.lock().unwrap(), vec![1]);
+
+        repo.transaction(|tx| {
+            tx.save_unlocked(&User::new("Committed via a transaction"));
+            Ok(())
+        }).expect("Transaction failed");
+        assert_eq!(*seen_ids.lock().unwrap(), vec![1, 2]);
+
+        let rolled_back: Result<(), rusqlite::Error> = repo.transaction(|tx| {
+            tx.save_unlocked(&User::new("Rolled back"));
+            Err(rusqlite::Error::QueryReturnedNoRows)
+        });
+        assert!(rolled_back.is_err());
+        assert_eq!(*seen_ids.lock().unwrap(), vec![1, 2]);
+    }
 }
\ No newline at end of file
@@ -1,10 +1,10 @@
-use std::time::Instant;
+use std::time::SystemTime;
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct Task {
     pub desc: String,
     pub tags: Vec<String>,
-    pub due:  Option<Instant>,
+    pub due:  Option<SystemTime>,
     pub done: bool,
 }
 
@@ -26,7 +26,7 @@ impl Task {
         self.done
     }
 
-    pub fn due(mut self, when: Instant) -> Self {
+    pub fn due(mut self, when: SystemTime) -> Self {
         self.due = Some(when);
         self
     }
@@ -62,8 +62,19 @@ impl User {
 #[derive(Debug)]
 pub enum UserSearchTerms {
     Name(String),
+    NameLike(String),
 }
 
 impl super::Searchable for User {
     type Credentials = UserSearchTerms;
 }
+
+#[derive(Debug)]
+pub enum TaskSearchTerms {
+    DescContains(String),
+    DueBefore(SystemTime),
+}
+
+impl super::Searchable for Task {
+    type Credentials = TaskSearchTerms;
+}
@@ -13,10 +13,16 @@ pub trait Repository<T> {
     fn all(&self) -> Vec<T>;
     fn get(&self, id: &Self::Id) -> Option<T>;
     fn save(&mut self, data: &T) -> Self::Id;
+
+    // Default is one `save` per item; implementations that can batch
+    // (e.g. a single multi-row INSERT) should override this.
+    fn save_all(&mut self, data: &[T]) -> Vec<Self::Id> {
+        data.iter().map(|item| self.save(item)).collect()
+    }
 }
 
 pub trait SearchableRepository<T: Searchable> : Repository<T> {
-    fn find(&self, id: &[T::Credentials]) -> Vec<T>;
+    fn find(&self, id: &[T::Credentials], limit: Option<u32>) -> Vec<T>;
 }
 
 // The structure is very ad-hoc
@@ -1,6 +1,6 @@
 #![feature(uniform_paths)]
 
-use std::time::{Duration, Instant};
+use std::time::{Duration, SystemTime};
 
 mod domain;
 mod data;
@@ -24,7 +24,7 @@ fn main() {
 
     let person: User = repo.get(&id).expect("No such person");
 
-    let mut buy_milk = Task::new("Buy Milk").due(Instant::now() + Duration::from_secs(60*24));
+    let mut buy_milk = Task::new("Buy Milk").due(SystemTime::now() + Duration::from_secs(60*24));
     buy_milk.tags = vec!["urgent".into()];
     let task_id = repo.save(&buy_milk);
 